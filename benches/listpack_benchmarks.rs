@@ -10,19 +10,104 @@ fn bench_push_back(c: &mut Criterion) {
         b.iter(|| {
             let mut lp = Listpack::new();
             for _ in 0..1000 {
-                lp.push_back(black_box(b"abc"));
+                lp.push_back(black_box(b"abc")).unwrap();
             }
         });
     });
 }
 
+/// Benchmarks for a pure push_back workload on a large list,
+/// which benefits from growth biased toward the back.
+fn bench_push_back_large(c: &mut Criterion) {
+    c.bench_function("push_back 100k small elements", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            let mut reallocs = 0;
+            let mut cap = lp.capacity();
+            for _ in 0..100_000 {
+                lp.push_back(black_box(b"abc")).unwrap();
+                if lp.capacity() != cap {
+                    cap = lp.capacity();
+                    reallocs += 1;
+                }
+            }
+            black_box(reallocs);
+        });
+    });
+}
+
+/// Benchmarks for building from a slice, against a push_back loop.
+fn bench_from_slices(c: &mut Criterion) {
+    let items: Vec<Vec<u8>> = (0..10_000).map(|i| format!("val{i}").into_bytes()).collect();
+
+    let mut group = c.benchmark_group("build_10k");
+
+    group.bench_function("push_back", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            for item in &items {
+                lp.push_back(black_box(item)).unwrap();
+            }
+            black_box(lp);
+        })
+    });
+
+    group.bench_function("from_slices", |b| {
+        b.iter(|| black_box(Listpack::from_slices(black_box(&items))))
+    });
+
+    group.finish();
+}
+
+/// Benchmarks for batch pushes, against a push_back loop. The
+/// loop reallocates several times; the batch grows once.
+fn bench_push_back_many(c: &mut Criterion) {
+    let items: Vec<Vec<u8>> = (0..10_000).map(|i| format!("val{i}").into_bytes()).collect();
+
+    let mut group = c.benchmark_group("push_10k");
+
+    group.bench_function("push_back loop", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            let mut reallocs = 0;
+            let mut cap = lp.capacity();
+            for item in &items {
+                lp.push_back(black_box(item)).unwrap();
+                if lp.capacity() != cap {
+                    cap = lp.capacity();
+                    reallocs += 1;
+                }
+            }
+            black_box(reallocs);
+        })
+    });
+
+    group.bench_function("push_back_many", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            lp.push_back_many(black_box(&items)).unwrap();
+            black_box(lp);
+        })
+    });
+
+    group.bench_function("push_front_many", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            lp.push_front_many(black_box(&items)).unwrap();
+            black_box(lp);
+        })
+    });
+
+    group.finish();
+}
+
 /// Benchmarks for push_front.
 fn bench_push_front(c: &mut Criterion) {
     c.bench_function("push_front 1000 small elements", |b| {
         b.iter(|| {
             let mut lp = Listpack::new();
             for _ in 0..1000 {
-                lp.push_front(black_box(b"abc"));
+                lp.push_front(black_box(b"abc")).unwrap();
             }
         });
     });
@@ -34,7 +119,7 @@ fn bench_pop_back(c: &mut Criterion) {
         b.iter(|| {
             let mut lp = Listpack::new();
             for _ in 0..1000 {
-                lp.push_back(b"abc");
+                lp.push_back(b"abc").unwrap();
             }
             for _ in 0..1000 {
                 black_box(lp.pop_back());
@@ -43,13 +128,28 @@ fn bench_pop_back(c: &mut Criterion) {
     });
 }
 
+/// Benchmarks for pop_back on a large list.
+fn bench_pop_back_large(c: &mut Criterion) {
+    c.bench_function("pop_back 100k elements", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            for _ in 0..100_000 {
+                lp.push_back(b"abc").unwrap();
+            }
+            while let Some(v) = lp.pop_back() {
+                black_box(v);
+            }
+        });
+    });
+}
+
 /// Benchmarks for pop_front.
 fn bench_pop_front(c: &mut Criterion) {
     c.bench_function("pop_front 1000 elements", |b| {
         b.iter(|| {
             let mut lp = Listpack::new();
             for _ in 0..1000 {
-                lp.push_back(b"abc");
+                lp.push_back(b"abc").unwrap();
             }
             for _ in 0..1000 {
                 black_box(lp.pop_front());
@@ -62,7 +162,7 @@ fn bench_pop_front(c: &mut Criterion) {
 fn bench_iterate(c: &mut Criterion) {
     let mut lp = Listpack::new();
     for _ in 0..1000 {
-        lp.push_back(b"abc");
+        lp.push_back(b"abc").unwrap();
     }
 
     c.bench_function("iterate over 1000 elements", |b| {
@@ -78,7 +178,7 @@ fn bench_iterate(c: &mut Criterion) {
 fn bench_get_random(c: &mut Criterion) {
     let mut lp = Listpack::new();
     for _ in 0..1000 {
-        lp.push_back(b"abc");
+        lp.push_back(b"abc").unwrap();
     }
 
     c.bench_function("get 100 random elements", |b| {
@@ -90,13 +190,53 @@ fn bench_get_random(c: &mut Criterion) {
     });
 }
 
+/// Benchmarks for random get on a large list, with and without
+/// the positional index.
+fn bench_get_random_indexed(c: &mut Criterion) {
+    let mut lp = Listpack::new();
+    for i in 0..100_000 {
+        lp.push_back(format!("val{i}").as_bytes()).unwrap();
+    }
+    // Deterministic pseudo-random indices.
+    let indices: Vec<usize> = (0..10_000u64)
+        .map(|i| (i.wrapping_mul(2_654_435_761) % 100_000) as usize)
+        .collect();
+
+    let mut group = c.benchmark_group("get_random_100k");
+    group.sample_size(10);
+
+    group.bench_function("linear", |b| {
+        b.iter(|| {
+            for &i in &indices {
+                black_box(lp.get(i));
+            }
+        })
+    });
+
+    let mut indexed = Listpack::new();
+    for i in 0..100_000 {
+        indexed.push_back(format!("val{i}").as_bytes()).unwrap();
+    }
+    indexed.build_index();
+
+    group.bench_function("indexed", |b| {
+        b.iter(|| {
+            for &i in &indices {
+                black_box(indexed.get(i));
+            }
+        })
+    });
+
+    group.finish();
+}
+
 /// Benchmarks for remove.
 fn bench_remove(c: &mut Criterion) {
     c.bench_function("remove 100 elements from middle", |b| {
         b.iter(|| {
             let mut lp = Listpack::new();
             for _ in 0..1000 {
-                lp.push_back(b"abc");
+                lp.push_back(b"abc").unwrap();
             }
             for _ in 0..100 {
                 lp.remove(black_box(500));
@@ -114,7 +254,7 @@ fn bench_push_integer(c: &mut Criterion) {
         b.iter(|| {
             let mut lp = Listpack::new();
             for i in -128i64..128 {
-                lp.push_integer(black_box(i));
+                lp.push_integer(black_box(i)).unwrap();
             }
         })
     });
@@ -124,7 +264,7 @@ fn bench_push_integer(c: &mut Criterion) {
         b.iter(|| {
             let mut lp = Listpack::new();
             for i in -32768i64..32768 {
-                lp.push_integer(black_box(i));
+                lp.push_integer(black_box(i)).unwrap();
             }
         })
     });
@@ -144,7 +284,7 @@ fn bench_push_integer(c: &mut Criterion) {
                 (1 << 23) - 1
             ];
             for &v in &values {
-                lp.push_integer(black_box(v));
+                lp.push_integer(black_box(v)).unwrap();
             }
         })
     });
@@ -162,7 +302,7 @@ fn bench_push_integer(c: &mut Criterion) {
                 i32::MAX as i64
             ];
             for &v in &values {
-                lp.push_integer(black_box(v));
+                lp.push_integer(black_box(v)).unwrap();
             }
         })
     });
@@ -170,6 +310,31 @@ fn bench_push_integer(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmarks for bulk integer loading against a push_integer loop.
+fn bench_extend_integers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("integer_bulk_10k");
+
+    group.bench_function("push_integer", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            for i in 0..10_000i64 {
+                lp.push_integer(black_box(i)).unwrap();
+            }
+            black_box(lp);
+        })
+    });
+
+    group.bench_function("extend_integers", |b| {
+        b.iter(|| {
+            let mut lp = Listpack::new();
+            lp.extend_integers(black_box(0..10_000i64)).unwrap();
+            black_box(lp);
+        })
+    });
+
+    group.finish();
+}
+
 /// Benchmarks for decode_integer.
 fn bench_decode_integer(c: &mut Criterion) {
     let mut group = c.benchmark_group("integer_decode");
@@ -190,7 +355,7 @@ fn bench_decode_integer(c: &mut Criterion) {
     ];
     
     for &v in &test_values {
-        lp.push_integer(v);
+        lp.push_integer(v).unwrap();
     }
 
     // Benchmark decoding.
@@ -205,6 +370,33 @@ fn bench_decode_integer(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmarks for varint encoding with and without a reused
+/// buffer.
+fn bench_encode_varint(c: &mut Criterion) {
+    let mut group = c.benchmark_group("varint_encode");
+
+    group.bench_function("encode_varint", |b| {
+        b.iter(|| {
+            for v in 0..10_000usize {
+                black_box(Listpack::encode_varint(black_box(v * 131)));
+            }
+        })
+    });
+
+    group.bench_function("encode_varint_into", |b| {
+        let mut buf = Vec::with_capacity(10);
+        b.iter(|| {
+            for v in 0..10_000usize {
+                buf.clear();
+                Listpack::encode_varint_into(black_box(v * 131), &mut buf);
+                black_box(&buf);
+            }
+        })
+    });
+
+    group.finish();
+}
+
 /// Benchmarks for mixed_operations.
 fn bench_mixed_operations(c: &mut Criterion) {
     let mut group = c.benchmark_group("mixed_operations");
@@ -215,8 +407,8 @@ fn bench_mixed_operations(c: &mut Criterion) {
             let mut lp = Listpack::new();
             // Add numbers and strings (less iterations).
             for i in -100i64..100 {
-                lp.push_integer(black_box(i));
-                lp.push_back(black_box(b"test"));
+                lp.push_integer(black_box(i)).unwrap();
+                lp.push_back(black_box(b"test")).unwrap();
             }
             // Remove everything
             while !lp.is_empty() {
@@ -250,7 +442,7 @@ fn bench_integer_encoding(c: &mut Criterion) {
         b.iter(|| {
             let mut lp = Listpack::new();
             for &v in &test_values {
-                lp.push_integer(black_box(v));
+                lp.push_integer(black_box(v)).unwrap();
             }
             for i in 0..lp.len() {
                 black_box(lp.decode_integer(lp.get(i).unwrap()).unwrap());
@@ -264,14 +456,21 @@ fn bench_integer_encoding(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_push_back,
+    bench_push_back_large,
+    bench_from_slices,
+    bench_push_back_many,
     bench_push_front,
     bench_pop_back,
+    bench_pop_back_large,
     bench_pop_front,
     bench_iterate,
     bench_get_random,
+    bench_get_random_indexed,
     bench_remove,
     bench_push_integer,
+    bench_extend_integers,
     bench_decode_integer,
+    bench_encode_varint,
     bench_mixed_operations,
     bench_integer_encoding
 );
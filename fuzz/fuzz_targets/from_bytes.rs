@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use listpack::Listpack;
+
+// Arbitrary input must either be rejected or produce a listpack
+// that validates and iterates in both directions without
+// panicking.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(lp) = Listpack::from_bytes(data) {
+        assert_eq!(lp.validate(), Ok(()));
+        assert_eq!(lp.iter().count(), lp.len());
+        assert_eq!(lp.iter().rev().count(), lp.len());
+        assert_eq!(lp.values().count(), lp.len());
+        assert_eq!(lp.as_bytes(), data);
+    }
+});